@@ -0,0 +1,74 @@
+use crate::namespaced_hash::{NamespaceId, NamespaceMerkleHasher, NamespacedHash};
+
+/// A compact commitment to a namespaced merkle tree that only remembers the `O(log n)`
+/// rightmost boundary nodes instead of every leaf.
+///
+/// The nodes are kept as a stack of completed perfect subtrees, left to right, in the same
+/// shape as an incremental/mountain-range construction. This lets a writer keep extending a
+/// tree with [`Self::push_leaf`] and read back the current [`Self::root`] without a backing
+/// `Db`, and lets a reader turn the leaves appended since some earlier size into the right-hand
+/// siblings a [`NamespaceProof`](crate::nmt_proof::NamespaceProof) needs via
+/// [`NamespaceProof::extend_right`](crate::nmt_proof::NamespaceProof::extend_right).
+#[derive(Debug, Clone)]
+pub struct Frontier<M, const NS_ID_SIZE: usize> {
+    hasher: M,
+    // Completed perfect subtrees, left to right, each paired with its height (0 = a leaf).
+    stack: Vec<(u32, NamespacedHash<NS_ID_SIZE>)>,
+    len: u32,
+}
+
+impl<M, const NS_ID_SIZE: usize> Frontier<M, NS_ID_SIZE>
+where
+    M: NamespaceMerkleHasher<Output = NamespacedHash<NS_ID_SIZE>>,
+{
+    pub fn new(hasher: M) -> Self {
+        Self {
+            hasher,
+            stack: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// The number of leaves pushed onto this frontier so far.
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Append one more leaf, merging completed perfect subtrees of equal height as they form.
+    pub fn push_leaf(&mut self, raw_leaf: &[u8], namespace: NamespaceId<NS_ID_SIZE>) {
+        let mut node = (0u32, NamespacedHash::hash_leaf(raw_leaf, namespace));
+        while let Some(&(top_height, _)) = self.stack.last() {
+            if top_height != node.0 {
+                break;
+            }
+            let (_, left) = self
+                .stack
+                .pop()
+                .expect("just checked the stack is non-empty");
+            node = (node.0 + 1, self.hasher.hash_nodes(&left, &node.1));
+        }
+        self.stack.push(node);
+        self.len += 1;
+    }
+
+    /// The current root commitment, or `None` if no leaves have been pushed yet.
+    pub fn root(&self) -> Option<NamespacedHash<NS_ID_SIZE>> {
+        let mut subtrees = self.stack.iter().rev();
+        let (_, mut acc) = subtrees.next()?.clone();
+        for (_, left) in subtrees {
+            acc = self.hasher.hash_nodes(left, &acc);
+        }
+        Some(acc)
+    }
+
+    /// The frontier's boundary nodes, left to right, each paired with its height (0 = a
+    /// leaf). These are exactly the siblings a range proof whose `end` sits at this
+    /// frontier's tip needs in order to cover the leaves pushed here.
+    pub fn boundary_nodes(&self) -> &[(u32, NamespacedHash<NS_ID_SIZE>)] {
+        &self.stack
+    }
+}
@@ -1,7 +1,13 @@
+use std::marker::PhantomData;
+
 use crate::{
+    frontier::Frontier,
     namespaced_hash::{NamespaceId, NamespaceMerkleHasher, NamespacedHash},
     simple_merkle::{
-        db::NoopDb, error::RangeProofError, proof::Proof, tree::MerkleHash,
+        db::NoopDb,
+        error::RangeProofError,
+        proof::{MultiRangeProof, Proof},
+        tree::MerkleHash,
         utils::compute_num_left_siblings,
     },
     NamespaceMerkleTree,
@@ -57,6 +63,21 @@ where
         raw_leaves: &[impl AsRef<[u8]>],
         leaf_namespace: NamespaceId<NS_ID_SIZE>,
     ) -> Result<(), RangeProofError> {
+        let computed_root = self.root_from_range(raw_leaves, leaf_namespace)?;
+        if &computed_root == root {
+            Ok(())
+        } else {
+            Err(RangeProofError::RootMismatch)
+        }
+    }
+
+    /// Reconstruct the root implied by this proof and the given leaves, without comparing it
+    /// against any expected root. See [`Proof::root_from_range`] for why this is useful.
+    pub fn root_from_range(
+        &self,
+        raw_leaves: &[impl AsRef<[u8]>],
+        leaf_namespace: NamespaceId<NS_ID_SIZE>,
+    ) -> Result<NamespacedHash<NS_ID_SIZE>, RangeProofError> {
         if self.is_of_absence() {
             return Err(RangeProofError::MalformedProof);
         };
@@ -73,8 +94,47 @@ where
         let tree = NamespaceMerkleTree::<NoopDb, M, NS_ID_SIZE>::with_hasher(
             M::with_ignore_max_ns(self.ignores_max_ns()),
         );
-        tree.inner
-            .check_range_proof(root, &leaf_hashes, &mut siblings, self.start_idx() as usize)
+        tree.inner.compute_root_from_range_proof(
+            &leaf_hashes,
+            &mut siblings,
+            self.start_idx() as usize,
+        )
+    }
+
+    /// Extend this proof's `end` so it verifies against the larger root produced once
+    /// `frontier_delta`'s leaves are appended, by folding in its boundary nodes as the new
+    /// right-hand siblings instead of regenerating the proof from scratch.
+    ///
+    /// `frontier_delta` must be a [`Frontier`] built from exactly the leaves appended after
+    /// this proof's current [`Self::end_idx`], in order.
+    ///
+    /// Only supported when the old tree's size (this proof's current `end`) is itself a power
+    /// of two. A `Frontier` built from only the newly appended leaves assumes they start a
+    /// perfect subtree of their own; when the old size isn't a power of two, the old tree has
+    /// a trailing leaf that isn't paired with anything yet, and the real tree re-groups it
+    /// with the new leaves instead of leaving it alone. This delta-only frontier has no way to
+    /// recompute that regrouping without the old tree's own boundary state, so such a proof is
+    /// rejected rather than silently extended with the wrong siblings.
+    pub fn extend_right(
+        &mut self,
+        frontier_delta: &Frontier<M, NS_ID_SIZE>,
+    ) -> Result<(), RangeProofError> {
+        let proof = match self {
+            NamespaceProof::AbsenceProof { proof, .. }
+            | NamespaceProof::PresenceProof { proof, .. } => proof,
+        };
+        if !frontier_extension_is_supported(proof.end) {
+            return Err(RangeProofError::FrontierNotAligned);
+        }
+        // `boundary_nodes` is largest-subtree-first (left to right), but right-hand siblings
+        // are folded in bottom-up, closest level first, same as everywhere else in this
+        // codebase (e.g. `MultiRangeProof`'s level-by-level walk) — so the frontier's own
+        // order has to be reversed here.
+        for (_, node) in frontier_delta.boundary_nodes().iter().rev() {
+            proof.siblings.push(node.clone());
+        }
+        proof.end += frontier_delta.len();
+        Ok(())
     }
 
     pub fn convert_to_absence_proof(&mut self, leaf: NamespacedHash<NS_ID_SIZE>) {
@@ -153,3 +213,462 @@ where
         !self.is_of_absence()
     }
 }
+
+/// Whether [`NamespaceProof::extend_right`] can safely reuse a delta-only frontier for a tree
+/// whose size (before the append) was `old_len`. See that method's docs for why a non-power-of-
+/// two size can't be supported this way.
+fn frontier_extension_is_supported(old_len: u32) -> bool {
+    old_len.is_power_of_two()
+}
+
+/// A proof of presence for several sorted, disjoint ranges of leaves against a single
+/// namespaced root, sharing siblings between ranges the same way [`MultiRangeProof`] does
+/// for the unnamespaced case.
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NamespaceMultiProof<M: MerkleHash, const NS_ID_SIZE: usize> {
+    pub proof: MultiRangeProof<M>,
+    pub ignore_max_ns: bool,
+}
+
+impl<M, const NS_ID_SIZE: usize> NamespaceMultiProof<M, NS_ID_SIZE>
+where
+    M: NamespaceMerkleHasher<Output = NamespacedHash<NS_ID_SIZE>>,
+{
+    /// Verify that `raw_leaves`, grouped and ordered the same way as the underlying
+    /// [`MultiRangeProof::ranges`], all belong to `leaf_namespace` and occur at the claimed
+    /// positions in a tree of `num_leaves` leaves with the given `root`.
+    pub fn verify_range(
+        &self,
+        root: &NamespacedHash<NS_ID_SIZE>,
+        raw_leaves: &[impl AsRef<[u8]>],
+        leaf_namespace: NamespaceId<NS_ID_SIZE>,
+        num_leaves: usize,
+    ) -> Result<(), RangeProofError> {
+        let leaf_hashes: Vec<_> = raw_leaves
+            .iter()
+            .map(|data| NamespacedHash::hash_leaf(data.as_ref(), leaf_namespace))
+            .collect();
+
+        let hasher = M::with_ignore_max_ns(self.ignore_max_ns);
+        let computed_root =
+            self.proof
+                .compute_root_with_hasher(&hasher, &leaf_hashes, num_leaves)?;
+
+        if &computed_root == root {
+            Ok(())
+        } else {
+            Err(RangeProofError::RootMismatch)
+        }
+    }
+
+    /// Verify that `raw_leaves` occur in `leaf_namespace` at the claimed positions, and that no
+    /// leaf belonging to that namespace was left out of any range's proven span.
+    ///
+    /// Mirrors [`NamespaceProof::verify_complete_namespace`] for a single range: for every
+    /// range that doesn't already reach an edge of the full tree, the single closest external
+    /// sibling on that side is checked against `leaf_namespace`, except on a side that the walk
+    /// joins directly to a neighbouring range's node; the gap between those two ranges is then
+    /// covered by the check on the neighbouring range's facing side. Namespaces are required to be
+    /// non-decreasing left to right, so if that closest sibling doesn't overlap the namespace,
+    /// nothing farther away on that side can either.
+    pub fn verify_complete_namespace(
+        &self,
+        root: &NamespacedHash<NS_ID_SIZE>,
+        raw_leaves: &[impl AsRef<[u8]>],
+        leaf_namespace: NamespaceId<NS_ID_SIZE>,
+        num_leaves: usize,
+    ) -> Result<(), RangeProofError> {
+        self.verify_range(root, raw_leaves, leaf_namespace, num_leaves)?;
+
+        for (left, right) in self.proof.boundary_sibling_indices(num_leaves)? {
+            for idx in [left, right].into_iter().flatten() {
+                let sibling = &self.siblings()[idx];
+                if sibling.min_namespace() <= leaf_namespace
+                    && leaf_namespace <= sibling.max_namespace()
+                {
+                    return Err(RangeProofError::NamespaceOrderingViolation);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn siblings(&self) -> &[NamespacedHash<NS_ID_SIZE>] {
+        self.proof.siblings()
+    }
+
+    pub fn ranges(&self) -> &[core::ops::Range<u32>] {
+        self.proof.ranges()
+    }
+}
+
+/// Proves that a namespaced tree with `new_len` leaves is an append-only extension of an
+/// earlier tree with `old_len` leaves, so that a light client which only kept the old root
+/// can trust that nothing it already verified was later changed.
+///
+/// This is the RFC 6962 consistency proof adapted to the namespaced hasher: besides checking
+/// the hash chain between the two roots, it also rejects an extension whose namespace range
+/// has narrowed, since leaves are required to be appended in non-decreasing namespace order.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConsistencyProof<M: MerkleHash, const NS_ID_SIZE: usize> {
+    pub old_len: u32,
+    pub new_len: u32,
+    pub nodes: Vec<NamespacedHash<NS_ID_SIZE>>,
+    pub ignore_max_ns: bool,
+    _marker: PhantomData<M>,
+}
+
+impl<M, const NS_ID_SIZE: usize> ConsistencyProof<M, NS_ID_SIZE>
+where
+    M: NamespaceMerkleHasher<Output = NamespacedHash<NS_ID_SIZE>>,
+{
+    pub fn new(
+        old_len: u32,
+        new_len: u32,
+        nodes: Vec<NamespacedHash<NS_ID_SIZE>>,
+        ignore_max_ns: bool,
+    ) -> Self {
+        Self {
+            old_len,
+            new_len,
+            nodes,
+            ignore_max_ns,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Build the consistency proof between a tree of `old_len` leaves and one of `new_len`
+    /// leaves, given every leaf hash of the *new* tree in order. This is the construction
+    /// [`NamespaceMerkleTree::build_consistency_proof`] runs once it has looked up the new
+    /// tree's leaves from its own `Db`.
+    pub fn build(
+        old_len: u32,
+        new_len: u32,
+        leaf_hashes: &[NamespacedHash<NS_ID_SIZE>],
+        ignore_max_ns: bool,
+    ) -> Result<Self, RangeProofError> {
+        if old_len > new_len || new_len as usize != leaf_hashes.len() {
+            return Err(RangeProofError::InconsistentTreeSizes);
+        }
+        // PROOF(0, D[0:n]) = [] in RFC 6962: an empty tree is consistent with anything, and
+        // there's no old subtree hash to derive, so this must be special-cased before
+        // recursing (the general recursion assumes at least one old leaf to split around).
+        if old_len == 0 {
+            return Ok(Self::new(old_len, new_len, Vec::new(), ignore_max_ns));
+        }
+        let hasher = M::with_ignore_max_ns(ignore_max_ns);
+        let nodes = rfc6962_consistency_proof(old_len as usize, leaf_hashes, &hasher);
+        Ok(Self::new(old_len, new_len, nodes, ignore_max_ns))
+    }
+
+    pub fn nodes(&self) -> &[NamespacedHash<NS_ID_SIZE>] {
+        &self.nodes
+    }
+
+    /// Verify that `new_root` is a consistent, append-only extension of `old_root`, for the
+    /// sizes `old_len`/`new_len` the verifier itself already trusts (e.g. `old_len` from its
+    /// own previous checkpoint, `new_len` from whatever it's being asked to adopt now).
+    ///
+    /// `old_len`/`new_len` are taken as explicit parameters rather than read off
+    /// `self.old_len`/`self.new_len` directly, since those fields come from the proof being
+    /// verified and can't be trusted on their own: a proof claiming `old_len: 0` would
+    /// otherwise vacuously succeed (RFC 6962's `PROOF(0, D[0:n]) = []`) against any `new_root`,
+    /// regardless of what the verifier actually expected `old_len` to be.
+    pub fn verify_consistency(
+        &self,
+        old_len: u32,
+        new_len: u32,
+        old_root: &NamespacedHash<NS_ID_SIZE>,
+        new_root: &NamespacedHash<NS_ID_SIZE>,
+    ) -> Result<(), RangeProofError> {
+        if self.old_len != old_len || self.new_len != new_len {
+            return Err(RangeProofError::InconsistentTreeSizes);
+        }
+        if self.old_len > self.new_len {
+            return Err(RangeProofError::InconsistentTreeSizes);
+        }
+        if self.old_len == self.new_len {
+            return if !self.nodes.is_empty() {
+                Err(RangeProofError::MalformedProof)
+            } else if old_root == new_root {
+                Ok(())
+            } else {
+                Err(RangeProofError::RootMismatch)
+            };
+        }
+        if self.old_len == 0 {
+            return if self.nodes.is_empty() {
+                Ok(())
+            } else {
+                Err(RangeProofError::MalformedProof)
+            };
+        }
+        if self.nodes.is_empty() {
+            return Err(RangeProofError::MalformedProof);
+        }
+
+        let hasher = M::with_ignore_max_ns(self.ignore_max_ns);
+        let mut proof = self.nodes.iter();
+
+        // Walk from the old tree's rightmost leaf up towards the root, folding in siblings
+        // from `proof` for both the old and the new root along the way. This is the
+        // bit-shifting form of the RFC 6962 verification algorithm: `node`/`last_node` track
+        // the old/new rightmost-leaf position at the current level.
+        let mut node = self.old_len - 1;
+        let mut last_node = self.new_len - 1;
+        while node & 1 == 1 {
+            node >>= 1;
+            last_node >>= 1;
+        }
+
+        let (mut old_hash, mut new_hash) = if node == 0 {
+            (old_root.clone(), old_root.clone())
+        } else {
+            let h = proof.next().ok_or(RangeProofError::MalformedProof)?.clone();
+            (h.clone(), h)
+        };
+
+        while node > 0 {
+            if node & 1 == 1 {
+                let sibling = proof.next().ok_or(RangeProofError::MalformedProof)?;
+                old_hash = hasher.hash_nodes(sibling, &old_hash);
+                new_hash = hasher.hash_nodes(sibling, &new_hash);
+            } else if node < last_node {
+                let sibling = proof.next().ok_or(RangeProofError::MalformedProof)?;
+                new_hash = hasher.hash_nodes(&new_hash, sibling);
+            }
+            node >>= 1;
+            last_node >>= 1;
+        }
+
+        for sibling in proof {
+            new_hash = hasher.hash_nodes(&new_hash, sibling);
+        }
+
+        if &old_hash != old_root {
+            return Err(RangeProofError::RootMismatch);
+        }
+        if &new_hash != new_root {
+            return Err(RangeProofError::RootMismatch);
+        }
+
+        if new_hash.min_namespace() != old_hash.min_namespace()
+            || new_hash.max_namespace() < old_hash.max_namespace()
+        {
+            return Err(RangeProofError::NamespaceOrderingViolation);
+        }
+
+        Ok(())
+    }
+}
+
+impl<Db, M, const NS_ID_SIZE: usize> NamespaceMerkleTree<Db, M, NS_ID_SIZE>
+where
+    M: NamespaceMerkleHasher<Output = NamespacedHash<NS_ID_SIZE>>,
+{
+    /// Build the consistency proof between this tree's state at `old_len` leaves and its
+    /// current state, reading the new tree's leaves straight out of its own `Db` instead of
+    /// requiring the caller to look them up and supply them the way [`ConsistencyProof::build`]
+    /// does.
+    pub fn build_consistency_proof(
+        &self,
+        old_len: u32,
+    ) -> Result<ConsistencyProof<M, NS_ID_SIZE>, RangeProofError> {
+        ConsistencyProof::build(
+            old_len,
+            self.len() as u32,
+            self.leaves(),
+            self.ignore_max_ns(),
+        )
+    }
+}
+
+/// `PROOF(m, D[0:n])` from RFC 6962, specialized to a namespaced hasher: the list of extra
+/// nodes a verifier needs, beyond `old_root` and `new_root`, to check that the first `m`
+/// leaves of `leaves` are an unchanged prefix.
+fn rfc6962_consistency_proof<M, const NS_ID_SIZE: usize>(
+    m: usize,
+    leaves: &[NamespacedHash<NS_ID_SIZE>],
+    hasher: &M,
+) -> Vec<NamespacedHash<NS_ID_SIZE>>
+where
+    M: NamespaceMerkleHasher<Output = NamespacedHash<NS_ID_SIZE>>,
+{
+    let n = leaves.len();
+    if m == n {
+        return Vec::new();
+    }
+    let k = largest_pow2_less_than(n);
+    if m <= k {
+        let mut proof = rfc6962_subproof(m, &leaves[..k], true, hasher);
+        proof.push(merkle_tree_hash(&leaves[k..], hasher));
+        proof
+    } else {
+        let mut proof = rfc6962_subproof(m - k, &leaves[k..], false, hasher);
+        proof.push(merkle_tree_hash(&leaves[..k], hasher));
+        proof
+    }
+}
+
+/// `SUBPROOF(m, D, b)` from RFC 6962.
+fn rfc6962_subproof<M, const NS_ID_SIZE: usize>(
+    m: usize,
+    leaves: &[NamespacedHash<NS_ID_SIZE>],
+    b: bool,
+    hasher: &M,
+) -> Vec<NamespacedHash<NS_ID_SIZE>>
+where
+    M: NamespaceMerkleHasher<Output = NamespacedHash<NS_ID_SIZE>>,
+{
+    let n = leaves.len();
+    if m == n {
+        return if b {
+            Vec::new()
+        } else {
+            vec![merkle_tree_hash(leaves, hasher)]
+        };
+    }
+    let k = largest_pow2_less_than(n);
+    if m <= k {
+        let mut proof = rfc6962_subproof(m, &leaves[..k], b, hasher);
+        proof.push(merkle_tree_hash(&leaves[k..], hasher));
+        proof
+    } else {
+        let mut proof = rfc6962_subproof(m - k, &leaves[k..], false, hasher);
+        proof.push(merkle_tree_hash(&leaves[..k], hasher));
+        proof
+    }
+}
+
+/// `MTH(D)` from RFC 6962: the merkle tree hash of a (non-empty) leaf hash list.
+fn merkle_tree_hash<M, const NS_ID_SIZE: usize>(
+    leaves: &[NamespacedHash<NS_ID_SIZE>],
+    hasher: &M,
+) -> NamespacedHash<NS_ID_SIZE>
+where
+    M: NamespaceMerkleHasher<Output = NamespacedHash<NS_ID_SIZE>>,
+{
+    match leaves {
+        [leaf] => leaf.clone(),
+        leaves => {
+            let k = largest_pow2_less_than(leaves.len());
+            let left = merkle_tree_hash(&leaves[..k], hasher);
+            let right = merkle_tree_hash(&leaves[k..], hasher);
+            hasher.hash_nodes(&left, &right)
+        }
+    }
+}
+
+/// The largest power of two strictly less than `n` (`n` must be at least 2).
+fn largest_pow2_less_than(n: usize) -> usize {
+    debug_assert!(n >= 2);
+    1 << (usize::BITS - (n - 1).leading_zeros() - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frontier_extension_rejects_unaligned_old_len() {
+        // old_len=5 (as in the D0..D7 example used to spec this out) isn't a power of two:
+        // D4 is a dangling unpaired leaf that a delta-only frontier can't re-group correctly.
+        assert!(!frontier_extension_is_supported(5));
+        assert!(!frontier_extension_is_supported(3));
+        assert!(!frontier_extension_is_supported(0));
+
+        assert!(frontier_extension_is_supported(1));
+        assert!(frontier_extension_is_supported(4));
+        assert!(frontier_extension_is_supported(8));
+    }
+
+    #[test]
+    fn extend_right_appends_boundary_nodes_in_bottom_up_order() {
+        // A hasher whose `hash_nodes` just keeps the left operand lets each combined node
+        // stay identifiable by its leftmost leaf, so the test can check *which* node ends up
+        // where in `proof.siblings` without needing a real cryptographic combinator.
+        #[derive(Clone)]
+        struct LeftOnlyHasher;
+
+        impl MerkleHash for LeftOnlyHasher {
+            type Output = NamespacedHash<1>;
+
+            fn empty_root(&self) -> Self::Output {
+                unreachable!("not exercised by this test")
+            }
+
+            fn hash_leaf(&self, _data: &[u8]) -> Self::Output {
+                unreachable!("namespaced leaves go through NamespacedHash::hash_leaf instead")
+            }
+
+            fn hash_nodes(&self, left: &Self::Output, _right: &Self::Output) -> Self::Output {
+                left.clone()
+            }
+        }
+
+        impl NamespaceMerkleHasher for LeftOnlyHasher {
+            fn with_ignore_max_ns(_ignore_max_ns: bool) -> Self {
+                LeftOnlyHasher
+            }
+        }
+
+        let ns = NamespaceId([7u8]);
+        let d4 = NamespacedHash::hash_leaf(&[4], ns);
+        let d5 = NamespacedHash::hash_leaf(&[5], ns);
+        let d6 = NamespacedHash::hash_leaf(&[6], ns);
+
+        let mut frontier = Frontier::new(LeftOnlyHasher);
+        frontier.push_leaf(&[4], ns);
+        frontier.push_leaf(&[5], ns);
+        frontier.push_leaf(&[6], ns);
+
+        // 3 appended leaves form 2 peaks, left to right: a height-1 subtree covering D4/D5
+        // (identified by its leftmost leaf D4, since `hash_nodes` keeps the left side), then
+        // the lone height-0 leaf D6.
+        assert_eq!(
+            frontier.boundary_nodes(),
+            &[(1, d4.clone()), (0, d6.clone())]
+        );
+
+        let mut proof = NamespaceProof::<LeftOnlyHasher, 1>::PresenceProof {
+            proof: Proof {
+                siblings: vec![d5.clone()],
+                start: 0,
+                end: 4,
+            },
+            ignore_max_ns: false,
+        };
+
+        proof
+            .extend_right(&frontier)
+            .expect("old_len=4 is a power of two");
+
+        // Bottom-up, closest level first: D6 (height 0) before the D4/D5 subtree (height 1) —
+        // the reverse of `boundary_nodes`'s largest-subtree-first order.
+        assert_eq!(proof.siblings(), &[d5, d6, d4]);
+        assert_eq!(proof.end_idx(), 7);
+    }
+
+    #[test]
+    fn largest_pow2_less_than_handles_every_k_in_its_own_range() {
+        // largest_pow2_less_than(n) must never reach all the way up to n itself, even right
+        // at a power-of-two boundary, since `rfc6962_consistency_proof`/`merkle_tree_hash`
+        // recurse on strictly smaller slices on both sides of the split.
+        assert_eq!(largest_pow2_less_than(2), 1);
+        assert_eq!(largest_pow2_less_than(3), 2);
+        assert_eq!(largest_pow2_less_than(4), 2);
+        assert_eq!(largest_pow2_less_than(5), 4);
+        assert_eq!(largest_pow2_less_than(8), 4);
+        assert_eq!(largest_pow2_less_than(9), 8);
+    }
+}
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use super::{
     db::NoopDb,
     error::RangeProofError,
@@ -31,6 +33,21 @@ where
         root: &M::Output,
         leaf_hashes: &[M::Output],
     ) -> Result<(), RangeProofError> {
+        let computed_root = self.root_from_range(leaf_hashes)?;
+        if &computed_root == root {
+            Ok(())
+        } else {
+            Err(RangeProofError::RootMismatch)
+        }
+    }
+
+    /// Reconstruct the root implied by this proof and the given leaves, without comparing it
+    /// against any expected root.
+    ///
+    /// This is what [`Self::verify_range`] does internally before comparing; exposing it lets
+    /// callers fold this proof into an outer commitment (e.g. a block header whose data root
+    /// is itself a leaf somewhere else) without re-deriving the root a second time.
+    pub fn root_from_range(&self, leaf_hashes: &[M::Output]) -> Result<M::Output, RangeProofError> {
         let tree = MerkleTree::<NoopDb, M>::new();
         let mut siblings = self.siblings.iter().collect();
 
@@ -38,7 +55,7 @@ where
             return Err(RangeProofError::WrongAmountOfLeavesProvided);
         }
 
-        tree.check_range_proof(root, leaf_hashes, &mut siblings, self.start as usize)
+        tree.compute_root_from_range_proof(leaf_hashes, &mut siblings, self.start as usize)
     }
 
     pub fn siblings(&self) -> &Vec<M::Output> {
@@ -75,3 +92,314 @@ where
         None
     }
 }
+
+/// A proof of presence for several sorted, disjoint ranges of leaves in a single tree.
+///
+/// Requesting a [`Proof`] per range forces the prover to repeat every sibling that two
+/// ranges happen to share. A `MultiRangeProof` instead runs a single Merkle multiproof walk
+/// over all the ranges at once, so the sibling list only grows with how spread out the
+/// ranges are, not with `ranges.len() * tree_height`.
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultiRangeProof<M: MerkleHash> {
+    pub siblings: Vec<M::Output>,
+    pub ranges: Vec<Range<u32>>,
+}
+
+impl<M> MultiRangeProof<M>
+where
+    M: MerkleHash,
+{
+    /// Verify that `leaf_hashes`, concatenated in the same order as [`Self::ranges`], occur
+    /// at the claimed positions in a tree of `num_leaves` leaves with the given `root`.
+    pub fn verify_range(
+        &self,
+        root: &M::Output,
+        leaf_hashes: &[M::Output],
+        num_leaves: usize,
+    ) -> Result<(), RangeProofError> {
+        let computed_root = self.compute_root(leaf_hashes, num_leaves)?;
+        if &computed_root == root {
+            Ok(())
+        } else {
+            Err(RangeProofError::RootMismatch)
+        }
+    }
+
+    /// Reconstruct the root implied by this proof and the given leaves, without comparing
+    /// it against any expected value.
+    pub fn compute_root(
+        &self,
+        leaf_hashes: &[M::Output],
+        num_leaves: usize,
+    ) -> Result<M::Output, RangeProofError> {
+        self.compute_root_with_hasher(&M::default(), leaf_hashes, num_leaves)
+    }
+
+    /// Same as [`Self::compute_root`], but hashes nodes with the given `hasher` instead of a
+    /// freshly constructed default one. Used by [`NamespaceMultiProof`](crate::nmt_proof::NamespaceMultiProof)
+    /// so that `ignore_max_ns` is honored consistently with the rest of the namespaced API.
+    pub(crate) fn compute_root_with_hasher(
+        &self,
+        hasher: &M,
+        leaf_hashes: &[M::Output],
+        num_leaves: usize,
+    ) -> Result<M::Output, RangeProofError> {
+        self.validate_ranges()?;
+
+        let expected_leaves: usize = self.ranges.iter().map(range_len).sum();
+        if leaf_hashes.len() != expected_leaves {
+            return Err(RangeProofError::WrongAmountOfLeavesProvided);
+        }
+
+        // The sorted list of (absolute_index, hash) for every leaf covered by `self.ranges`.
+        let mut nodes: Vec<(usize, M::Output)> = Vec::with_capacity(expected_leaves);
+        let mut leaf_hashes = leaf_hashes.iter().cloned();
+        for range in &self.ranges {
+            for idx in range.clone() {
+                let hash = leaf_hashes
+                    .next()
+                    .expect("leaf count was already checked above");
+                nodes.push((idx as usize, hash));
+            }
+        }
+
+        let mut siblings = self.siblings.iter();
+        let mut level_len = num_leaves;
+
+        while level_len > 1 {
+            let mut next_level = Vec::with_capacity(nodes.len().div_ceil(2).max(1));
+            let mut i = 0;
+            while i < nodes.len() {
+                let (pos, ref hash) = nodes[i];
+                let sibling_is_known =
+                    pos % 2 == 0 && nodes.get(i + 1).is_some_and(|(p, _)| *p == pos + 1);
+                // When `level_len` is odd, its last node (always at an even position) has no
+                // sibling at all in this level's tree shape, so it must be carried up unhashed
+                // instead of paired with a sibling pulled from the proof.
+                let has_no_real_sibling = pos % 2 == 0 && pos + 1 >= level_len;
+
+                let parent = if sibling_is_known {
+                    let right = &nodes[i + 1].1;
+                    i += 2;
+                    hasher.hash_nodes(hash, right)
+                } else if has_no_real_sibling {
+                    i += 1;
+                    hash.clone()
+                } else {
+                    let sibling = siblings.next().ok_or(RangeProofError::MalformedProof)?;
+                    i += 1;
+                    if pos % 2 == 0 {
+                        hasher.hash_nodes(hash, sibling)
+                    } else {
+                        hasher.hash_nodes(sibling, hash)
+                    }
+                };
+                next_level.push((pos / 2, parent));
+            }
+            nodes = next_level;
+            level_len = level_len.div_ceil(2);
+        }
+
+        if siblings.next().is_some() {
+            return Err(RangeProofError::MalformedProof);
+        }
+
+        match nodes.len() {
+            1 => Ok(nodes.into_iter().next().expect("checked len == 1").1),
+            0 => Err(RangeProofError::NoLeavesProvided),
+            _ => Err(RangeProofError::MalformedProof),
+        }
+    }
+
+    fn validate_ranges(&self) -> Result<(), RangeProofError> {
+        if self.ranges.is_empty() {
+            return Err(RangeProofError::NoLeavesProvided);
+        }
+        if self.ranges.iter().any(|r| r.start >= r.end) {
+            return Err(RangeProofError::MalformedProof);
+        }
+        if self.ranges.windows(2).any(|w| w[0].end > w[1].start) {
+            return Err(RangeProofError::RangesNotSorted);
+        }
+        Ok(())
+    }
+
+    /// For each range, the index into [`Self::siblings`] of the closest external sibling
+    /// immediately outside that range on the left and on the right, or `None` on a side that
+    /// already touches an edge of the full `num_leaves`-leaf tree, or whose edge node the walk
+    /// pairs directly with a node it already knows from a neighbouring range.
+    ///
+    /// This mirrors [`Proof::leftmost_right_sibling`]/[`Proof::rightmost_left_sibling`] for the
+    /// single-range case, generalized to a fold that shares siblings across several ranges:
+    /// siblings can't be split into one clean left/right group per range the way a single
+    /// range's can, so this walks the same level-by-level fold [`Self::compute_root_with_hasher`]
+    /// does, without needing real hashes, just to find which sibling closes each edge.
+    pub(crate) fn boundary_sibling_indices(
+        &self,
+        num_leaves: usize,
+    ) -> Result<Vec<(Option<usize>, Option<usize>)>, RangeProofError> {
+        self.validate_ranges()?;
+
+        let mut left_edges: Vec<Option<usize>> = self
+            .ranges
+            .iter()
+            .map(|r| (r.start > 0).then_some(r.start as usize))
+            .collect();
+        let mut right_edges: Vec<Option<usize>> = self
+            .ranges
+            .iter()
+            .map(|r| (r.end > 0 && (r.end as usize) < num_leaves).then_some(r.end as usize - 1))
+            .collect();
+        let mut found: Vec<(Option<usize>, Option<usize>)> = vec![(None, None); self.ranges.len()];
+
+        let mut positions: Vec<usize> = self
+            .ranges
+            .iter()
+            .flat_map(|r| r.clone())
+            .map(|idx| idx as usize)
+            .collect();
+        let mut sibling_idx = 0usize;
+        let mut level_len = num_leaves;
+
+        while level_len > 1 {
+            let mut next_positions = Vec::with_capacity(positions.len().div_ceil(2).max(1));
+            let mut i = 0;
+            while i < positions.len() {
+                let pos = positions[i];
+                let sibling_is_known =
+                    pos % 2 == 0 && positions.get(i + 1).is_some_and(|p| *p == pos + 1);
+                let has_no_real_sibling = pos % 2 == 0 && pos + 1 >= level_len;
+
+                if sibling_is_known {
+                    i += 2;
+                } else if has_no_real_sibling {
+                    i += 1;
+                } else {
+                    if sibling_idx >= self.siblings.len() {
+                        return Err(RangeProofError::MalformedProof);
+                    }
+                    if pos % 2 == 1 {
+                        for (idx, edge) in left_edges.iter().enumerate() {
+                            if found[idx].0.is_none() && *edge == Some(pos) {
+                                found[idx].0 = Some(sibling_idx);
+                            }
+                        }
+                    } else {
+                        for (idx, edge) in right_edges.iter().enumerate() {
+                            if found[idx].1.is_none() && *edge == Some(pos) {
+                                found[idx].1 = Some(sibling_idx);
+                            }
+                        }
+                    }
+                    sibling_idx += 1;
+                    i += 1;
+                }
+                next_positions.push(pos / 2);
+            }
+            positions = next_positions;
+            for edge in left_edges.iter_mut() {
+                *edge = edge.map(|e| e / 2);
+            }
+            for edge in right_edges.iter_mut() {
+                *edge = edge.map(|e| e / 2);
+            }
+            level_len = level_len.div_ceil(2);
+        }
+
+        Ok(found)
+    }
+
+    pub fn siblings(&self) -> &Vec<M::Output> {
+        &self.siblings
+    }
+
+    pub fn ranges(&self) -> &Vec<Range<u32>> {
+        &self.ranges
+    }
+}
+
+fn range_len(range: &Range<u32>) -> usize {
+    range.end.saturating_sub(range.start) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default, Clone)]
+    struct TestHasher;
+
+    impl MerkleHash for TestHasher {
+        type Output = Vec<u8>;
+
+        fn empty_root(&self) -> Self::Output {
+            Vec::new()
+        }
+
+        fn hash_leaf(&self, data: &[u8]) -> Self::Output {
+            let mut out = vec![0u8];
+            out.extend_from_slice(data);
+            out
+        }
+
+        fn hash_nodes(&self, left: &Self::Output, right: &Self::Output) -> Self::Output {
+            let mut out = vec![1u8];
+            out.extend_from_slice(left);
+            out.extend_from_slice(right);
+            out
+        }
+    }
+
+    #[test]
+    fn multi_range_proof_handles_non_power_of_two_leaf_count() {
+        let hasher = TestHasher;
+        let leaves: Vec<_> = (0..5u8).map(|b| hasher.hash_leaf(&[b])).collect();
+
+        // MTH(D0..D4) = H(H(H(D0,D1),H(D2,D3)), D4): the trailing odd leaf is carried up
+        // unhashed instead of being paired with a phantom sibling.
+        let h01 = hasher.hash_nodes(&leaves[0], &leaves[1]);
+        let h23 = hasher.hash_nodes(&leaves[2], &leaves[3]);
+        let h0123 = hasher.hash_nodes(&h01, &h23);
+        let root = hasher.hash_nodes(&h0123, &leaves[4]);
+
+        let proof = MultiRangeProof::<TestHasher> {
+            siblings: vec![h0123],
+            ranges: vec![4..5],
+        };
+        let computed = proof
+            .compute_root(&[leaves[4].clone()], 5)
+            .expect("a single real sibling should be enough to reconstruct the root");
+        assert_eq!(computed, root);
+    }
+
+    #[test]
+    fn multi_range_proof_dedupes_a_sibling_shared_between_two_ranges() {
+        let hasher = TestHasher;
+        let leaves: Vec<_> = (0..8u8).map(|b| hasher.hash_leaf(&[b])).collect();
+
+        let h01 = hasher.hash_nodes(&leaves[0], &leaves[1]);
+        let h23 = hasher.hash_nodes(&leaves[2], &leaves[3]);
+        let h0123 = hasher.hash_nodes(&h01, &h23);
+        let h45 = hasher.hash_nodes(&leaves[4], &leaves[5]);
+        let h67 = hasher.hash_nodes(&leaves[6], &leaves[7]);
+        let h4567 = hasher.hash_nodes(&h45, &h67);
+        let root = hasher.hash_nodes(&h0123, &h4567);
+
+        // Proving leaf 0 and leaf 2 in isolation would each need their own copy of `h4567`
+        // (3 siblings apiece); folding both ranges through the same multiproof walk means
+        // `h4567` is supplied only once.
+        let proof = MultiRangeProof::<TestHasher> {
+            siblings: vec![leaves[1].clone(), leaves[3].clone(), h4567],
+            ranges: vec![0..1, 2..3],
+        };
+        let computed = proof
+            .compute_root(&[leaves[0].clone(), leaves[2].clone()], 8)
+            .expect("3 siblings should be enough to cover both ranges, with h4567 shared");
+        assert_eq!(computed, root);
+    }
+}